@@ -1,10 +1,193 @@
 #![feature(core, collections)]
 
+use std::cmp::{self, Ordering};
 use std::fmt::{self, Debug, Formatter};
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+/// Number of bytes that a `NibbleData` can hold inline before it spills onto the heap.
+///
+/// Trie keys are typically short, so most `NibbleVec`s never allocate.
+const INLINE_CAPACITY: usize = 32;
+
+/// The backing store for a `NibbleVec`.
+///
+/// Behaves like a `Vec<u8>`, but keeps its bytes inline (on the stack) until it needs room
+/// for more than `INLINE_CAPACITY` of them, at which point it moves onto the heap. Once on
+/// the heap it stays there, even if it later shrinks.
+enum NibbleData {
+    Inline([u8; INLINE_CAPACITY], usize),
+    Heap(Vec<u8>)
+}
+
+impl NibbleData {
+    fn new() -> NibbleData {
+        NibbleData::Inline([0; INLINE_CAPACITY], 0)
+    }
+
+    fn with_capacity(cap: usize) -> NibbleData {
+        if cap <= INLINE_CAPACITY {
+            NibbleData::new()
+        } else {
+            NibbleData::Heap(Vec::with_capacity(cap))
+        }
+    }
+
+    fn from_vec(vec: Vec<u8>) -> NibbleData {
+        if vec.len() <= INLINE_CAPACITY {
+            let mut buf = [0u8; INLINE_CAPACITY];
+            buf[..vec.len()].copy_from_slice(&vec);
+            NibbleData::Inline(buf, vec.len())
+        } else {
+            NibbleData::Heap(vec)
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        match *self {
+            NibbleData::Inline(..) => INLINE_CAPACITY,
+            NibbleData::Heap(ref v) => v.capacity()
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self[..]
+    }
+
+    fn push(&mut self, val: u8) {
+        if let NibbleData::Heap(ref mut v) = *self {
+            v.push(val);
+            return;
+        }
+        if let NibbleData::Inline(ref mut buf, ref mut len) = *self {
+            if *len < INLINE_CAPACITY {
+                buf[*len] = val;
+                *len += 1;
+                return;
+            }
+        }
+        // Inline storage is full: spill onto the heap.
+        let mut vec = Vec::with_capacity(INLINE_CAPACITY * 2);
+        vec.extend_from_slice(&self[..]);
+        vec.push(val);
+        *self = NibbleData::Heap(vec);
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        match *self {
+            NibbleData::Inline(ref buf, ref mut len) => {
+                if *len == 0 {
+                    None
+                } else {
+                    *len -= 1;
+                    Some(buf[*len])
+                }
+            }
+            NibbleData::Heap(ref mut v) => v.pop()
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        let len = self.len();
+        if len + additional <= self.capacity() {
+            return;
+        }
+        if let NibbleData::Heap(ref mut v) = *self {
+            v.reserve(additional);
+            return;
+        }
+        let mut vec = Vec::with_capacity(len + additional);
+        vec.extend_from_slice(&self[..]);
+        *self = NibbleData::Heap(vec);
+    }
+
+    fn push_all(&mut self, other: &[u8]) {
+        self.reserve(other.len());
+        match *self {
+            NibbleData::Inline(ref mut buf, ref mut len) => {
+                buf[*len..*len + other.len()].copy_from_slice(other);
+                *len += other.len();
+            }
+            NibbleData::Heap(ref mut v) => v.extend_from_slice(other)
+        }
+    }
+
+    /// Bulk-copy `len` bytes from `src` into this (empty) buffer.
+    ///
+    /// **Panics** (in debug builds) if this buffer is non-empty or lacks the capacity.
+    unsafe fn copy_from(&mut self, src: *const u8, len: usize) {
+        debug_assert_eq!(self.len(), 0);
+        debug_assert!(self.capacity() >= len);
+        match *self {
+            NibbleData::Inline(ref mut buf, ref mut out_len) => {
+                ptr::copy_nonoverlapping(src, buf.as_mut_ptr(), len);
+                *out_len = len;
+            }
+            NibbleData::Heap(ref mut v) => {
+                ptr::copy_nonoverlapping(src, v.as_mut_ptr(), len);
+                v.set_len(len);
+            }
+        }
+    }
+
+    /// Shorten the buffer, dropping any bytes beyond `new_len`.
+    fn truncate(&mut self, new_len: usize) {
+        match *self {
+            NibbleData::Inline(_, ref mut len) => *len = cmp::min(*len, new_len),
+            NibbleData::Heap(ref mut v) => v.truncate(new_len)
+        }
+    }
+}
+
+impl Deref for NibbleData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match *self {
+            NibbleData::Inline(ref buf, len) => &buf[..len],
+            NibbleData::Heap(ref v) => &v[..]
+        }
+    }
+}
+
+impl DerefMut for NibbleData {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match *self {
+            NibbleData::Inline(ref mut buf, len) => &mut buf[..len],
+            NibbleData::Heap(ref mut v) => &mut v[..]
+        }
+    }
+}
+
+impl PartialEq for NibbleData {
+    fn eq(&self, other: &NibbleData) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl Eq for NibbleData {}
+
+impl Clone for NibbleData {
+    fn clone(&self) -> NibbleData {
+        match *self {
+            NibbleData::Inline(buf, len) => NibbleData::Inline(buf, len),
+            NibbleData::Heap(ref v) => NibbleData::Heap(v.clone())
+        }
+    }
+}
+
+impl Debug for NibbleData {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&self[..], fmt)
+    }
+}
 
 /// A data-structure for storing a sequence of 4-bit values.
 ///
-/// Values are stored in a `Vec<u8>`, with two values per byte.
+/// Values are stored two per byte, in a small-vector that lives entirely on the stack
+/// until it needs room for more than `INLINE_CAPACITY` bytes, at which point it spills
+/// onto the heap.
 ///
 /// Values at even indices are stored in the most-significant half of their byte,
 /// while values at odd indices are stored in the least-significant half.
@@ -17,7 +200,7 @@ use std::fmt::{self, Debug, Formatter};
 /// * If the length is odd, then the second half of the last byte must be 0.
 pub struct NibbleVec {
     length: usize,
-    data: Vec<u8>
+    data: NibbleData
 }
 
 impl NibbleVec {
@@ -25,7 +208,7 @@ impl NibbleVec {
     pub fn new() -> NibbleVec {
         NibbleVec {
             length: 0,
-            data: Vec::new()
+            data: NibbleData::new()
         }
     }
 
@@ -36,10 +219,28 @@ impl NibbleVec {
         let length = 2 * vec.len();
         NibbleVec {
             length: length,
-            data: vec
+            data: NibbleData::from_vec(vec)
         }
     }
 
+    /// Create an empty nibble vector with room for at least `n` nibbles without reallocating.
+    pub fn with_capacity(n: usize) -> NibbleVec {
+        NibbleVec {
+            length: 0,
+            data: NibbleData::with_capacity((n + 1) / 2)
+        }
+    }
+
+    /// Get the number of nibbles that can be held before the vector reallocates.
+    pub fn capacity(&self) -> usize {
+        self.data.capacity() * 2
+    }
+
+    /// Reserve capacity for at least `additional` more nibbles to be pushed.
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve((additional + 1) / 2);
+    }
+
     /// Get the number of elements stored in the vector.
     pub fn len(&self) -> usize {
         self.length
@@ -103,7 +304,7 @@ impl NibbleVec {
     #[inline(always)]
     fn split_odd(&mut self, idx: usize) -> NibbleVec {
         let tail_vec_size = (self.length - idx) / 2;
-        let mut tail = NibbleVec::from_byte_vec(Vec::with_capacity(tail_vec_size));
+        let mut tail = NibbleVec { length: 0, data: NibbleData::with_capacity(tail_vec_size) };
 
         // Perform an overlap copy, copying the last nibble of the original vector only if
         // the length of the new tail is *odd*.
@@ -128,23 +329,18 @@ impl NibbleVec {
     /// Split function for even *indices*.
     #[inline(always)]
     fn split_even(&mut self, idx: usize) -> NibbleVec {
-        // Avoid allocating a temporary vector by copying all the bytes in order, then popping them.
+        // The split point is byte-aligned, so the tail's bytes are a contiguous, already
+        // correctly-packed run of self.data. Bulk-copy them in one go rather than pushing
+        // and popping byte by byte.
+        let byte_idx = idx / 2;
+        let tail_byte_len = self.data.len() - byte_idx;
 
-        // Possible to prove: l_d - ⌊i / 2⌋ = ⌊(l_v - i + 1) / 2⌋
-        //  where l_d = self.data.len()
-        //        l_v = self.length
-        let tail_vec_size = (self.length - idx + 1) / 2;
-        let mut tail = NibbleVec::from_byte_vec(Vec::with_capacity(tail_vec_size));
+        let mut tail = NibbleVec { length: 0, data: NibbleData::with_capacity(tail_byte_len) };
 
-        // Copy the bytes.
-        for i in range(idx / 2, self.data.len()) {
-            tail.data.push(self.data[i]);
-        }
-
-        // Pop the same bytes.
-        for _ in range(idx / 2, self.data.len()) {
-            self.data.pop();
+        unsafe {
+            tail.data.copy_from(self.data[byte_idx..].as_ptr(), tail_byte_len);
         }
+        self.data.truncate(byte_idx);
 
         // Update lengths.
         tail.length = self.length - idx;
@@ -157,7 +353,7 @@ impl NibbleVec {
     /// self.data[end - 1]. The second half of the last entry is included
     /// if include_last is true.
     #[inline(always)]
-    fn overlap_copy(&self, start: usize, end: usize, vec: &mut Vec<u8>, length: &mut usize, include_last: bool) {
+    fn overlap_copy(&self, start: usize, end: usize, vec: &mut NibbleData, length: &mut usize, include_last: bool) {
         // Copy up to the first half of the last byte.
         for i in range(start, end - 1) {
             // The first half is the second half of the old entry.
@@ -201,6 +397,218 @@ impl NibbleVec {
 
         self
     }
+
+    /// Obtain a borrowed view over the whole vector.
+    pub fn as_slice(&self) -> NibbleSlice {
+        NibbleSlice {
+            data: &self.data,
+            offset: 0,
+            length: self.length
+        }
+    }
+
+    /// Iterate over the nibbles stored in this vector, in order.
+    pub fn iter(&self) -> Iter {
+        Iter { vec: self, idx: 0 }
+    }
+
+    /// Count the number of leading nibbles that `self` and `other` have in common.
+    pub fn common_prefix_len(&self, other: &NibbleVec) -> usize {
+        self.as_slice().common_prefix(&other.as_slice())
+    }
+
+    /// Encode this nibble sequence using hex-prefix encoding, as used for trie node keys.
+    ///
+    /// The first byte's high nibble carries two flags: bit 1 (`0b10`) is set when `is_leaf`
+    /// is true, and bit 0 (`0b01`) is set when the nibble count is odd. When the count is
+    /// odd, the first data nibble is folded into the low half of that flag byte so that the
+    /// remaining nibbles fall on byte boundaries.
+    pub fn encoded(&self, is_leaf: bool) -> Vec<u8> {
+        let is_odd = self.len() % 2 == 1;
+
+        let mut flag: u8 = if is_leaf { 0b10 } else { 0b00 };
+
+        let mut result = Vec::with_capacity(self.len() / 2 + 1);
+
+        if is_odd {
+            flag |= 0b01;
+            result.push((flag << 4) | self.get(0));
+
+            for i in 0..self.len() / 2 {
+                result.push((self.get(2 * i + 1) << 4) | self.get(2 * i + 2));
+            }
+        } else {
+            result.push(flag << 4);
+
+            for i in 0..self.len() / 2 {
+                result.push((self.get(2 * i) << 4) | self.get(2 * i + 1));
+            }
+        }
+
+        result
+    }
+
+    /// Decode a hex-prefix encoded nibble sequence, returning the nibbles and the leaf flag.
+    ///
+    /// This is the inverse of `encoded`.
+    pub fn from_encoded(data: &[u8]) -> (NibbleVec, bool) {
+        let flag = data[0] >> 4;
+        let is_leaf = flag & 0b10 != 0;
+        let is_odd = flag & 0b01 != 0;
+
+        let mut result = NibbleVec::new();
+
+        if is_odd {
+            result.push(data[0] & 0x0F);
+        }
+
+        for &byte in &data[1..] {
+            result.push(byte >> 4);
+            result.push(byte & 0x0F);
+        }
+
+        (result, is_leaf)
+    }
+}
+
+/// An iterator over the nibbles stored in a `NibbleVec`, yielded in order.
+pub struct Iter<'a> {
+    vec: &'a NibbleVec,
+    idx: usize
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.idx >= self.vec.len() {
+            None
+        } else {
+            let val = self.vec.get(self.idx);
+            self.idx += 1;
+            Some(val)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.vec.len() - self.idx;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> IntoIterator for &'a NibbleVec {
+    type Item = u8;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<u8> for NibbleVec {
+    /// Build a `NibbleVec` out of an iterator of individual nibbles, masking each value to
+    /// its low 4 bits.
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> NibbleVec {
+        let mut result = NibbleVec::new();
+        for val in iter {
+            result.push(val & 0x0F);
+        }
+        result
+    }
+}
+
+impl From<Vec<u8>> for NibbleVec {
+    /// Build a `NibbleVec` from a vector of individual nibbles, one value per element.
+    ///
+    /// This is distinct from `from_byte_vec`, which instead treats the input as already
+    /// packed two nibbles per byte.
+    fn from(nibbles: Vec<u8>) -> NibbleVec {
+        nibbles.into_iter().collect()
+    }
+}
+
+/// A borrowed view onto a range of nibbles.
+///
+/// Wraps a byte slice together with a starting nibble offset, so that sub-ranges of a
+/// nibble sequence can be examined without copying. This is the workhorse for trie-style
+/// traversal, where a shared prefix is repeatedly chopped off a key.
+#[derive(Clone, Copy)]
+pub struct NibbleSlice<'a> {
+    data: &'a [u8],
+    /// Offset from the start of `data`, measured in nibbles.
+    offset: usize,
+    length: usize
+}
+
+impl<'a> NibbleSlice<'a> {
+    /// Create a slice covering every nibble in `data`.
+    pub fn new(data: &'a [u8]) -> NibbleSlice<'a> {
+        NibbleSlice::new_offset(data, 0)
+    }
+
+    /// Create a slice covering every nibble in `data`, starting at `offset`.
+    pub fn new_offset(data: &'a [u8], offset: usize) -> NibbleSlice<'a> {
+        let total_length = data.len() * 2;
+        if offset > total_length {
+            panic!("attempted to offset past slice end. len is {}, offset is {}", total_length, offset);
+        }
+        NibbleSlice {
+            data: data,
+            offset: offset,
+            length: total_length - offset
+        }
+    }
+
+    /// Get the number of nibbles covered by this slice.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Fetch a single nibble from the slice.
+    ///
+    /// **Panics** if `idx >= self.len()`.
+    pub fn at(&self, idx: usize) -> u8 {
+        if idx >= self.length {
+            panic!("attempted access beyond slice end. len is {}, index is {}", self.length, idx);
+        }
+        let real_idx = self.offset + idx;
+        match real_idx % 2 {
+            0 => self.data[real_idx / 2] >> 4,
+            _ => self.data[real_idx / 2] & 0x0F
+        }
+    }
+
+    /// Return a new slice, advanced past the first `n` nibbles of this one.
+    ///
+    /// **Panics** if `n > self.len()`.
+    pub fn mid(&self, n: usize) -> NibbleSlice<'a> {
+        if n > self.length {
+            panic!("attempted to advance past slice end. len is {}, n is {}", self.length, n);
+        }
+        NibbleSlice {
+            data: self.data,
+            offset: self.offset + n,
+            length: self.length - n
+        }
+    }
+
+    /// Check whether `self` begins with every nibble of `other`.
+    pub fn starts_with(&self, other: &NibbleSlice) -> bool {
+        self.common_prefix(other) == other.len()
+    }
+
+    /// Count the number of leading nibbles that `self` and `other` have in common.
+    pub fn common_prefix(&self, other: &NibbleSlice) -> usize {
+        let min_length = cmp::min(self.len(), other.len());
+        let mut count = 0;
+        for i in 0..min_length {
+            if self.at(i) != other.at(i) {
+                break;
+            }
+            count += 1;
+        }
+        count
+    }
 }
 
 impl PartialEq<NibbleVec> for NibbleVec {
@@ -212,6 +620,30 @@ impl PartialEq<NibbleVec> for NibbleVec {
 
 impl Eq for NibbleVec {}
 
+impl PartialOrd for NibbleVec {
+    fn partial_cmp(&self, other: &NibbleVec) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NibbleVec {
+    /// Compare two nibble vectors lexicographically, nibble by nibble.
+    ///
+    /// A raw byte comparison of `data` is not enough: the odd-length invariant zeroes the
+    /// second half of a trailing byte, so `[8]` and `[8, 0]` share the same bytes. Here a
+    /// vector that is a proper prefix of another always compares as less.
+    fn cmp(&self, other: &NibbleVec) -> Ordering {
+        let min_len = cmp::min(self.len(), other.len());
+        for i in 0..min_len {
+            match self.get(i).cmp(&other.get(i)) {
+                Ordering::Equal => continue,
+                order => return order
+            }
+        }
+        self.len().cmp(&other.len())
+    }
+}
+
 impl PartialEq<[u8]> for NibbleVec {
     fn eq(&self, other: &[u8]) -> bool {
         if other.len() != self.len() {
@@ -253,7 +685,7 @@ impl Debug for NibbleVec {
 
 #[cfg(test)]
 mod test {
-    use NibbleVec;
+    use {NibbleVec, NibbleSlice};
 
     fn v8_7_6_5() -> NibbleVec {
         NibbleVec::from_byte_vec(vec![8 << 4 | 7, 6 << 4 | 5])
@@ -285,6 +717,59 @@ mod test {
         }
     }
 
+    #[test]
+    fn nibble_slice_basic() {
+        let bytes = [0x12, 0x34, 0x56];
+        let slice = NibbleSlice::new(&bytes);
+        assert_eq!(slice.len(), 6);
+        assert_eq!(slice.at(0), 1);
+        assert_eq!(slice.at(1), 2);
+        assert_eq!(slice.at(5), 6);
+    }
+
+    #[test]
+    fn nibble_slice_offset() {
+        let bytes = [0x12, 0x34, 0x56];
+        let slice = NibbleSlice::new_offset(&bytes, 2);
+        assert_eq!(slice.len(), 4);
+        assert_eq!(slice.at(0), 3);
+        assert_eq!(slice.at(3), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn nibble_slice_offset_out_of_range() {
+        let bytes = [0x12, 0x34, 0x56];
+        NibbleSlice::new_offset(&bytes, 7);
+    }
+
+    #[test]
+    fn nibble_slice_mid() {
+        let bytes = [0x12, 0x34, 0x56];
+        let slice = NibbleSlice::new(&bytes);
+
+        let mid = slice.mid(2);
+        assert_eq!(mid.len(), 4);
+        assert_eq!(mid.at(0), 3);
+
+        // `mid` chains: advancing twice by 2 is the same as advancing once by 4.
+        let mid2 = mid.mid(2);
+        assert_eq!(mid2.len(), 2);
+        assert_eq!(mid2.at(0), 5);
+        assert_eq!(mid2.at(0), slice.mid(4).at(0));
+    }
+
+    #[test]
+    fn nibble_slice_starts_with() {
+        let bytes = [0x12, 0x34, 0x56];
+        let prefix_bytes = [0x12];
+        let non_prefix_bytes = [0x12, 0x99];
+
+        let slice = NibbleSlice::new(&bytes);
+        assert!(slice.starts_with(&NibbleSlice::new(&prefix_bytes)));
+        assert!(!slice.starts_with(&NibbleSlice::new(&non_prefix_bytes)));
+    }
+
     fn split_test(  nibble_vec: &NibbleVec,
                     idx: usize,
                     first: Vec<u8>,
@@ -313,6 +798,41 @@ mod test {
         split_test(&odd_length, 3, vec![11, 10, 9], vec![]);
     }
 
+    /// `split_even`'s bulk-copy fast path should produce byte-exact results when the tail
+    /// fits inline (`tail_byte_len <= INLINE_CAPACITY`).
+    #[test]
+    fn split_even_copy_from_inline_tail() {
+        let data = vec![0x12u8, 0x34, 0x56, 0x78];
+        let mut nv = NibbleVec::from_byte_vec(data);
+        let tail = nv.split(2);
+
+        assert_eq!(nv.len(), 2);
+        assert_eq!(tail.len(), 6);
+        assert!(nv == [1, 2][..]);
+        assert!(tail == [3, 4, 5, 6, 7, 8][..]);
+    }
+
+    /// `split_even`'s bulk-copy fast path should produce byte-exact results when the tail
+    /// spills onto the heap (`tail_byte_len > INLINE_CAPACITY`), checking every nibble
+    /// individually rather than relying solely on aggregate `==`.
+    #[test]
+    fn split_even_copy_from_heap_tail() {
+        let nibbles: Vec<u8> = (0..150).map(|i| (i % 16) as u8).collect();
+        let mut nv: NibbleVec = nibbles.iter().cloned().collect();
+
+        let tail = nv.split(4);
+
+        assert_eq!(nv.len(), 4);
+        assert_eq!(tail.len(), nibbles.len() - 4);
+
+        for i in 0..nv.len() {
+            assert_eq!(nv.get(i), nibbles[i]);
+        }
+        for i in 0..tail.len() {
+            assert_eq!(tail.get(i), nibbles[4 + i]);
+        }
+    }
+
     /// Join vec2 onto vec1 and ensure that the results matches the one expected.
     fn join_test(vec1: &NibbleVec, vec2: &NibbleVec, result: Vec<u8>) {
         let joined = vec1.clone().join(vec2);
@@ -355,4 +875,176 @@ mod test {
         vec = vec.join(&NibbleVec::from_byte_vec(vec![1 << 4 | 3, 5 << 4]));
         assert_eq!(vec.get(1), 1);
     }
+
+    /// `INLINE_CAPACITY` is 32 bytes (64 nibbles); push well past that to force a spill
+    /// onto the heap, and make sure the public API still behaves once it has.
+    #[test]
+    fn heap_backed_push() {
+        let data: Vec<u8> = (0..100).map(|i| (i % 16) as u8).collect();
+
+        let mut nv = NibbleVec::new();
+        for &val in &data {
+            nv.push(val);
+        }
+
+        assert_eq!(nv.len(), data.len());
+        for (i, &val) in data.iter().enumerate() {
+            assert_eq!(nv.get(i), val);
+        }
+
+        let cloned = nv.clone();
+        assert!(nv == cloned);
+        assert!(nv == data[..]);
+        assert!(cloned == data[..]);
+    }
+
+    /// Splitting and joining a heap-backed vector should round-trip, exercising the
+    /// bulk-copy and truncation paths for a tail that is itself heap-backed.
+    #[test]
+    fn heap_backed_split_join() {
+        let data: Vec<u8> = (0..100).map(|i| (i % 16) as u8).collect();
+
+        let mut nv = NibbleVec::new();
+        for &val in &data {
+            nv.push(val);
+        }
+
+        let mut head = nv.clone();
+        let tail = head.split(10);
+        assert!(head == data[..10]);
+        assert!(tail == data[10..]);
+
+        let rejoined = head.join(&tail);
+        assert!(rejoined == data[..]);
+    }
+
+    /// `reserve` must be able to grow a vector straight onto the heap from empty.
+    #[test]
+    fn heap_backed_reserve() {
+        let mut nv = NibbleVec::new();
+        nv.reserve(100);
+        assert!(nv.capacity() >= 100);
+
+        for i in 0..70u8 {
+            nv.push(i % 16);
+        }
+        assert_eq!(nv.len(), 70);
+    }
+
+    /// Exercise `NibbleData`'s heap-backed `Clone`, `PartialEq` and `Debug` directly,
+    /// since they're otherwise only reached indirectly through `NibbleVec`.
+    #[test]
+    fn heap_backed_nibble_data() {
+        use super::{NibbleData, INLINE_CAPACITY};
+
+        let mut data = NibbleData::new();
+        for i in 0..(INLINE_CAPACITY as u8 + 5) {
+            data.push(i);
+        }
+        assert_eq!(data.len(), INLINE_CAPACITY + 5);
+
+        let cloned = data.clone();
+        assert_eq!(data, cloned);
+        assert_eq!(format!("{:?}", data), format!("{:?}", cloned));
+    }
+
+    #[test]
+    fn iter_round_trip() {
+        let nv = v8_7_6_5();
+
+        let collected: Vec<u8> = nv.iter().collect();
+        assert_eq!(collected, vec![8, 7, 6, 5]);
+
+        let mut via_for_loop = vec![];
+        for n in &nv {
+            via_for_loop.push(n);
+        }
+        assert_eq!(via_for_loop, vec![8, 7, 6, 5]);
+    }
+
+    #[test]
+    fn from_iter_collect() {
+        let nv: NibbleVec = vec![1u8, 2, 3].into_iter().collect();
+        assert!(nv == [1, 2, 3][..]);
+    }
+
+    #[test]
+    fn from_vec_masks_to_low_nibble() {
+        // `From<Vec<u8>>` takes one nibble per element, distinct from `from_byte_vec`'s
+        // two-nibbles-per-byte packing.
+        let nv = NibbleVec::from(vec![8, 7, 6, 5]);
+        assert!(nv == v8_7_6_5());
+
+        // Values are masked to their low 4 bits.
+        let masked = NibbleVec::from(vec![200, 7]);
+        assert_eq!(masked.get(0), 200u8 & 0x0F);
+        assert_eq!(masked.get(1), 7);
+    }
+
+    #[test]
+    fn ord() {
+        let short = v8_7_6_5();
+        let mut long = short.clone();
+        long.push(0);
+
+        // A vector is less than one which shares its prefix but is longer.
+        assert!(short < long);
+        assert_eq!(short.cmp(&short.clone()), ::std::cmp::Ordering::Equal);
+
+        // Differences are resolved at the first mismatched nibble, not by raw length.
+        let larger = v11_10_9();
+        assert!(short < larger);
+    }
+
+    #[test]
+    fn common_prefix_len() {
+        let v1 = v8_7_6_5();
+        let mut v2 = v8_7_6_5();
+        v2.push(3);
+
+        assert_eq!(v1.common_prefix_len(&v2), v1.len());
+        assert_eq!(v2.common_prefix_len(&v1), v1.len());
+        assert_eq!(v1.common_prefix_len(&v11_10_9()), 0);
+    }
+
+    #[test]
+    fn hex_prefix_round_trip() {
+        for &is_leaf in &[true, false] {
+            let even = v8_7_6_5();
+            let (decoded_even, leaf_even) = NibbleVec::from_encoded(&even.encoded(is_leaf));
+            assert!(decoded_even == even);
+            assert_eq!(leaf_even, is_leaf);
+
+            let odd = v11_10_9();
+            let (decoded_odd, leaf_odd) = NibbleVec::from_encoded(&odd.encoded(is_leaf));
+            assert!(decoded_odd == odd);
+            assert_eq!(leaf_odd, is_leaf);
+        }
+    }
+
+    #[test]
+    fn with_capacity() {
+        let nv = NibbleVec::with_capacity(10);
+        assert_eq!(nv.len(), 0);
+        assert!(nv.capacity() >= 10);
+    }
+
+    #[test]
+    fn reserve() {
+        let mut nv = NibbleVec::new();
+        nv.reserve(100);
+        assert!(nv.capacity() >= 100);
+    }
+
+    #[test]
+    fn hex_prefix_flag_nibble() {
+        // Even length, not a leaf: flag nibble is 0b0000.
+        assert_eq!(v8_7_6_5().encoded(false)[0] >> 4, 0b0000);
+        // Even length, leaf: flag nibble is 0b0010.
+        assert_eq!(v8_7_6_5().encoded(true)[0] >> 4, 0b0010);
+        // Odd length, not a leaf: flag nibble is 0b0001.
+        assert_eq!(v11_10_9().encoded(false)[0] >> 4, 0b0001);
+        // Odd length, leaf: flag nibble is 0b0011.
+        assert_eq!(v11_10_9().encoded(true)[0] >> 4, 0b0011);
+    }
 }